@@ -0,0 +1,95 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use image::DynamicImage;
+
+/// Identifies a cache entry derived from a source URL.
+///
+/// `Failed` is stored alongside `Orig` so that URLs which 404 or fail to
+/// decode are remembered and short-circuited instead of being retried on
+/// every render.
+#[derive(Hash)]
+enum UrlKey<'a> {
+    Orig(&'a str),
+    Failed(&'a str),
+}
+
+impl UrlKey<'_> {
+    fn hash_u64(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+pub enum CacheEntry {
+    Image(DynamicImage),
+    Failed,
+}
+
+/// On-disk cache for downloaded thumbnail textures, keyed by source URL.
+pub struct ImageCache {
+    dir: PathBuf,
+}
+
+impl ImageCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path_for(&self, key: UrlKey) -> PathBuf {
+        self.dir.join(format!("{:016x}", key.hash_u64()))
+    }
+
+    pub fn get(&self, url: &str) -> Option<CacheEntry> {
+        if self.path_for(UrlKey::Failed(url)).exists() {
+            return Some(CacheEntry::Failed);
+        }
+
+        let bytes = std::fs::read(self.path_for(UrlKey::Orig(url))).ok()?;
+        image::load_from_memory(&bytes).ok().map(CacheEntry::Image)
+    }
+
+    pub fn store(&self, url: &str, img: &DynamicImage) -> Result<()> {
+        let mut bytes = Vec::new();
+        img.write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+        std::fs::write(self.path_for(UrlKey::Orig(url)), bytes)?;
+        Ok(())
+    }
+
+    pub fn store_failed(&self, url: &str) -> Result<()> {
+        std::fs::write(self.path_for(UrlKey::Failed(url)), [])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache() -> ImageCache {
+        let dir = std::env::temp_dir().join(format!(
+            "lavie-cache-test-{:?}-{}",
+            std::thread::current().id(),
+            std::process::id()
+        ));
+        ImageCache::new(dir).unwrap()
+    }
+
+    #[test]
+    fn failed_url_short_circuits_instead_of_being_retried() {
+        let cache = temp_cache();
+        let url = "https://example.invalid/broken.jpg";
+
+        assert!(cache.get(url).is_none());
+
+        cache.store_failed(url).unwrap();
+
+        assert!(matches!(cache.get(url), Some(CacheEntry::Failed)));
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use reqwest::Client;
+
+use crate::cache::{CacheEntry, ImageCache};
+
+#[derive(serde::Deserialize)]
+pub struct BlogPost {
+    pub title: String,
+    pub thumb: Option<Thumbnail>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+    pub rendition: Option<Rendition>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Rendition {
+    pub url: String,
+}
+
+pub async fn fetch_blog_post(client: &Client, base_url: &str, slug: &str) -> Result<BlogPost> {
+    println!("Fetching");
+    let url = format!("{}/api/blog?slug={}", base_url, slug);
+
+    let response = client
+        .get(&url)
+        .send()
+        .await?
+        .json::<BlogPost>()
+        .await
+        .context("Failed to parse blog post")?;
+
+    Ok(response)
+}
+
+pub async fn load_image(client: &Client, cache: &ImageCache, url: &str) -> Result<DynamicImage> {
+    match cache.get(url) {
+        Some(CacheEntry::Image(img)) => return Ok(img),
+        Some(CacheEntry::Failed) => anyhow::bail!("Cached failure for {}", url),
+        None => {}
+    }
+
+    println!("Loading texture {}", url);
+    let result = fetch_and_decode(client, url).await;
+
+    match result {
+        Ok(img) => {
+            let _ = cache.store(url, &img);
+            Ok(img)
+        }
+        Err(err) => {
+            let _ = cache.store_failed(url);
+            Err(err)
+        }
+    }
+}
+
+async fn fetch_and_decode(client: &Client, url: &str) -> Result<DynamicImage> {
+    let response = client
+        .get(url)
+        .send()
+        .await?
+        .bytes()
+        .await
+        .context("Failed to download image bytes")?;
+
+    let img = image::load_from_memory(&response).context("Failed to load image from memory")?;
+
+    Ok(img)
+}
@@ -0,0 +1,144 @@
+use std::io::{Cursor, Write};
+
+use anyhow::{Context, Error, Result};
+use image::RgbaImage;
+use jpeg_encoder::{ColorType, Encoder};
+use raqote::DrawTarget;
+use rayon::prelude::*;
+
+/// Output image format for a rendered card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Jpeg,
+    Png,
+    WebP,
+}
+
+impl OutputFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            OutputFormat::Jpeg => "image/jpeg",
+            OutputFormat::Png => "image/png",
+            OutputFormat::WebP => "image/webp",
+        }
+    }
+
+    /// Parse a format from a `?format=` query value, falling back to JPEG
+    /// when unrecognized.
+    pub fn from_str_lossy(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "png" | "image/png" => OutputFormat::Png,
+            "webp" | "image/webp" => OutputFormat::WebP,
+            _ => OutputFormat::Jpeg,
+        }
+    }
+
+    /// Parse a format out of a composite `Accept` header, e.g.
+    /// `image/avif,image/webp,*/*;q=0.8`, by checking each comma-separated
+    /// media-type token (ignoring its `;q=...` weight) in turn. Returns
+    /// `None` if no token names a format we can produce.
+    pub fn from_accept_header(value: &str) -> Option<Self> {
+        value.split(',').find_map(|part| {
+            let media_type = part.split(';').next()?.trim();
+            match media_type.to_ascii_lowercase().as_str() {
+                "image/png" => Some(OutputFormat::Png),
+                "image/webp" => Some(OutputFormat::WebP),
+                "image/jpeg" | "image/jpg" => Some(OutputFormat::Jpeg),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// Encode a finished `DrawTarget` into `w` in the given format.
+pub fn render_to(
+    w: &mut dyn Write,
+    dt: DrawTarget,
+    format: OutputFormat,
+    quality: u8,
+) -> Result<(), Error> {
+    match format {
+        OutputFormat::Jpeg => encode_jpeg(w, dt, quality),
+        OutputFormat::Png => encode_rgba(w, dt, image::ImageFormat::Png),
+        OutputFormat::WebP => encode_rgba(w, dt, image::ImageFormat::WebP),
+    }
+}
+
+/// JPEG has no alpha channel, so RGB is un-premultiplied and alpha discarded.
+fn encode_jpeg(w: &mut dyn Write, dt: DrawTarget, quality: u8) -> Result<(), Error> {
+    let (width, height) = (dt.width() as usize, dt.height() as usize);
+
+    let data = dt
+        .into_inner()
+        .par_iter()
+        .flat_map(|&pixel| {
+            let alpha = ((pixel >> 24) & 0xFF) as f32 / 255.0;
+            if alpha == 0.0 {
+                return [0u8, 0u8, 0u8];
+            }
+
+            [
+                (((pixel >> 16) & 0xFF) as f32 / alpha).min(255.0) as u8,
+                (((pixel >> 8) & 0xFF) as f32 / alpha).min(255.0) as u8,
+                ((pixel & 0xFF) as f32 / alpha).min(255.0) as u8,
+            ]
+        })
+        .collect::<Vec<u8>>();
+
+    let mut encoder = Encoder::new(w, quality);
+    encoder.set_optimized_huffman_tables(true);
+    encoder.encode(&data, width as u16, height as u16, ColorType::Rgb)?;
+
+    Ok(())
+}
+
+/// PNG/WebP keep the real alpha channel, so overlays and the gradient
+/// composite correctly instead of being flattened onto an opaque background.
+fn encode_rgba(w: &mut dyn Write, dt: DrawTarget, format: image::ImageFormat) -> Result<(), Error> {
+    let (width, height) = (dt.width() as u32, dt.height() as u32);
+
+    let data = dt
+        .into_inner()
+        .par_iter()
+        .flat_map(|&pixel| {
+            let alpha = ((pixel >> 24) & 0xFF) as f32 / 255.0;
+            if alpha == 0.0 {
+                return [0u8, 0u8, 0u8, 0u8];
+            }
+
+            [
+                (((pixel >> 16) & 0xFF) as f32 / alpha).min(255.0) as u8,
+                (((pixel >> 8) & 0xFF) as f32 / alpha).min(255.0) as u8,
+                ((pixel & 0xFF) as f32 / alpha).min(255.0) as u8,
+                (alpha * 255.0) as u8,
+            ]
+        })
+        .collect::<Vec<u8>>();
+
+    let img = RgbaImage::from_raw(width, height, data)
+        .context("Failed to build RGBA buffer from draw target")?;
+
+    let mut bytes = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgba8(img).write_to(&mut bytes, format)?;
+    w.write_all(&bytes.into_inner())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_accept_header_picks_the_first_recognized_media_type() {
+        assert_eq!(
+            OutputFormat::from_accept_header("image/avif,image/webp;q=0.8"),
+            Some(OutputFormat::WebP)
+        );
+    }
+
+    #[test]
+    fn from_accept_header_returns_none_when_nothing_is_recognized() {
+        assert_eq!(OutputFormat::from_accept_header("image/avif,*/*"), None);
+    }
+}
@@ -0,0 +1,172 @@
+use raqote::{Color, SolidSource};
+use serde::{de, Deserialize, Deserializer};
+
+/// An ARGB color. Deserialized from a hex string (`"RRGGBB"` or
+/// `"RRGGBBAA"`, with or without a leading `#`) so it can be carried as a
+/// single scalar value in a query string as well as in a JSON body.
+#[derive(Debug, Clone, Copy)]
+pub struct RgbaColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl RgbaColor {
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let hex = hex.trim_start_matches('#');
+        let byte = |range: std::ops::Range<usize>| -> Result<u8, String> {
+            hex.get(range.clone())
+                .ok_or_else(|| format!("invalid color hex: {}", hex))
+                .and_then(|s| {
+                    u8::from_str_radix(s, 16).map_err(|_| format!("invalid color hex: {}", hex))
+                })
+        };
+
+        match hex.len() {
+            6 => Ok(Self {
+                r: byte(0..2)?,
+                g: byte(2..4)?,
+                b: byte(4..6)?,
+                a: 255,
+            }),
+            8 => Ok(Self {
+                r: byte(0..2)?,
+                g: byte(2..4)?,
+                b: byte(4..6)?,
+                a: byte(6..8)?,
+            }),
+            _ => Err(format!("invalid color hex: {}", hex)),
+        }
+    }
+
+    pub fn solid_source(self) -> SolidSource {
+        SolidSource::from_unpremultiplied_argb(self.a, self.r, self.g, self.b)
+    }
+
+    pub fn gradient_color(self) -> Color {
+        Color::new(self.a, self.r, self.g, self.b)
+    }
+}
+
+impl<'de> Deserialize<'de> for RgbaColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let hex = String::deserialize(deserializer)?;
+        RgbaColor::from_hex(&hex).map_err(de::Error::custom)
+    }
+}
+
+fn default_background() -> RgbaColor {
+    RgbaColor {
+        r: 248,
+        g: 249,
+        b: 250,
+        a: 255,
+    }
+}
+
+fn default_gradient_start() -> RgbaColor {
+    RgbaColor {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 128,
+    }
+}
+
+fn default_gradient_end() -> RgbaColor {
+    RgbaColor {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 128,
+    }
+}
+
+fn default_text_color() -> RgbaColor {
+    RgbaColor {
+        r: 255,
+        g: 0,
+        b: 255,
+        a: 255,
+    }
+}
+
+/// Explicit content and styling for a card render, parsed from a query
+/// string or a small JSON body instead of fetched from the blog backend.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CardParams {
+    pub title: String,
+    pub subtitle: Option<String>,
+    #[serde(default = "default_background")]
+    pub background: RgbaColor,
+    #[serde(default = "default_gradient_start")]
+    pub gradient_start: RgbaColor,
+    #[serde(default = "default_gradient_end")]
+    pub gradient_end: RgbaColor,
+    #[serde(default = "default_text_color")]
+    pub text_color: RgbaColor,
+}
+
+impl Default for CardParams {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            subtitle: None,
+            background: default_background(),
+            gradient_start: default_gradient_start(),
+            gradient_end: default_gradient_end(),
+            text_color: default_text_color(),
+        }
+    }
+}
+
+impl From<&crate::client::BlogPost> for CardParams {
+    fn from(blog: &crate::client::BlogPost) -> Self {
+        Self {
+            title: blog.title.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hex_parses_a_six_digit_color_as_opaque() {
+        let color = RgbaColor::from_hex("336699").unwrap();
+        assert_eq!(
+            (color.r, color.g, color.b, color.a),
+            (0x33, 0x66, 0x99, 255)
+        );
+    }
+
+    #[test]
+    fn from_hex_parses_an_eight_digit_color_with_explicit_alpha() {
+        let color = RgbaColor::from_hex("336699cc").unwrap();
+        assert_eq!(
+            (color.r, color.g, color.b, color.a),
+            (0x33, 0x66, 0x99, 0xcc)
+        );
+    }
+
+    #[test]
+    fn from_hex_accepts_a_leading_hash() {
+        let color = RgbaColor::from_hex("#336699").unwrap();
+        assert_eq!(
+            (color.r, color.g, color.b, color.a),
+            (0x33, 0x66, 0x99, 255)
+        );
+    }
+
+    #[test]
+    fn from_hex_rejects_the_wrong_length_or_invalid_digits() {
+        assert!(RgbaColor::from_hex("336").is_err());
+        assert!(RgbaColor::from_hex("zzzzzz").is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use font_kit::family_name::FamilyName;
+use font_kit::font::Font;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+use raqote::{AntialiasMode, DrawOptions, DrawTarget, GradientStop, Image, Point, Source};
+use rayon::prelude::*;
+
+use crate::cache::ImageCache;
+use crate::client::{load_image, BlogPost};
+use crate::format::{render_to, OutputFormat};
+use crate::params::CardParams;
+use crate::text::{fit_title, wrap};
+
+pub const WIDTH: i32 = 1200;
+pub const HEIGHT: i32 = 630;
+const TEXT_MARGIN: f32 = 64.0;
+const SUBTITLE_FONT_SIZE: f32 = 32.0;
+const SUBTITLE_GAP: f32 = 16.0;
+
+/// Requested card dimensions, e.g. a wide OG card or a square avatar.
+#[derive(Debug, Clone, Copy)]
+pub struct CardSize {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for CardSize {
+    fn default() -> Self {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+        }
+    }
+}
+
+/// Scale `img` (up or down) with a Lanczos filter and center-crop it to exactly
+/// `width` x `height`, i.e. resize-and-cover. Returns `None` for a
+/// zero-dimension source, where the cover scale would be infinite and the
+/// `as u32` cast would saturate to `u32::MAX`, triggering a multi-exabyte
+/// allocation in `resize_exact`.
+fn resize_cover(img: &image::DynamicImage, width: u32, height: u32) -> Option<image::DynamicImage> {
+    if img.width() == 0 || img.height() == 0 {
+        return None;
+    }
+
+    let (src_width, src_height) = (img.width() as f32, img.height() as f32);
+    let scale = (width as f32 / src_width).max(height as f32 / src_height);
+    let scaled_width = (src_width * scale).round() as u32;
+    let scaled_height = (src_height * scale).round() as u32;
+
+    let scaled = img.resize_exact(
+        scaled_width,
+        scaled_height,
+        image::imageops::FilterType::Lanczos3,
+    );
+
+    let x = (scaled_width.saturating_sub(width)) / 2;
+    let y = (scaled_height.saturating_sub(height)) / 2;
+
+    Some(scaled.crop_imm(x, y, width, height))
+}
+
+/// Resolve the system's best-match sans-serif font once at startup, rather
+/// than on every request. Returns an error (instead of panicking) when the
+/// host has no matching font installed, e.g. a minimal container image.
+pub fn load_system_font() -> Result<Font> {
+    SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .context("No sans-serif system font found")?
+        .load()
+        .context("Failed to load system font")
+}
+
+pub fn u8rgba_u32argb(img: &image::ImageBuffer<image::Rgba<u8>, Vec<u8>>) -> Vec<u32> {
+    let mut target = Vec::with_capacity(img.len());
+    img.par_chunks(4)
+        .map(|chunk| {
+            let r = chunk[0] as u32;
+            let g = chunk[1] as u32;
+            let b = chunk[2] as u32;
+            let a = chunk[3] as u32;
+            (a << 24) | (r << 16) | (g << 8) | b
+        })
+        .collect_into_vec(&mut target);
+    target
+}
+
+/// Render a card driven by a `BlogPost` fetched from the backend, drawing
+/// its thumbnail rendition (if any) behind the title.
+pub async fn render_thumbnail(
+    w: &mut dyn Write,
+    client: &reqwest::Client,
+    cache: &ImageCache,
+    font: &Font,
+    blog: &BlogPost,
+    format: OutputFormat,
+    size: CardSize,
+) -> Result<()> {
+    // A missing, 404ing, or cached-failed rendition should just mean no
+    // background image behind the title — not a failed response.
+    let image = match &blog.thumb {
+        Some(thumb) => match &thumb.rendition {
+            Some(rendition) => load_image(client, cache, &rendition.url).await.ok(),
+            None => None,
+        },
+        None => None,
+    };
+
+    render_card(
+        w,
+        font,
+        &CardParams::from(blog),
+        image.as_ref(),
+        format,
+        size,
+    )
+}
+
+/// Render a card entirely from explicit parameters, with no backend
+/// round-trip and no background image.
+pub fn render_params(
+    w: &mut dyn Write,
+    font: &Font,
+    params: &CardParams,
+    format: OutputFormat,
+    size: CardSize,
+) -> Result<()> {
+    render_card(w, font, params, None, format, size)
+}
+
+fn render_card(
+    w: &mut dyn Write,
+    font: &Font,
+    params: &CardParams,
+    image: Option<&image::DynamicImage>,
+    format: OutputFormat,
+    size: CardSize,
+) -> Result<()> {
+    let mut dt = DrawTarget::new(size.width, size.height);
+    let mut draw_o = DrawOptions::new();
+    draw_o.antialias = AntialiasMode::Gray;
+    draw_o.alpha = 1.0;
+
+    // Background
+    dt.fill_rect(
+        0.0,
+        0.0,
+        size.width as f32,
+        size.height as f32,
+        &Source::Solid(params.background.solid_source()),
+        &draw_o,
+    );
+
+    // Draw the cover-fitted background image, if any. A zero-dimension
+    // source (e.g. a corrupt decode) just means no background image, same
+    // as a missing or cached-failed rendition.
+    if let Some(cover) =
+        image.and_then(|image| resize_cover(image, size.width as u32, size.height as u32))
+    {
+        let rem = u8rgba_u32argb(&cover.to_rgba8());
+        dt.draw_image_at(
+            0.0,
+            0.0,
+            &Image {
+                width: cover.width() as i32,
+                height: cover.height() as i32,
+                data: &rem,
+            },
+            &draw_o,
+        );
+    }
+
+    // Gradient overlay from `gradient_start` to `gradient_end`
+    let gradient = raqote::Gradient {
+        stops: vec![
+            GradientStop {
+                position: 0.0,
+                color: params.gradient_start.gradient_color(),
+            },
+            GradientStop {
+                position: 1.0,
+                color: params.gradient_end.gradient_color(),
+            },
+        ],
+    };
+
+    dt.fill_rect(
+        0.0,
+        0.0,
+        size.width as f32,
+        size.height as f32,
+        &Source::new_linear_gradient(
+            gradient,
+            Point::zero(),
+            Point::new(size.width as f32, size.height as f32),
+            raqote::Spread::Repeat,
+        ),
+        &draw_o,
+    );
+
+    let safe_width = size.width as f32 - 2.0 * TEXT_MARGIN;
+    let safe_height = size.height as f32 - 2.0 * TEXT_MARGIN;
+
+    // Reserve room for the subtitle so fit_title doesn't size the title to
+    // fill the whole safe area and push the subtitle off the card.
+    let subtitle_lines = params
+        .subtitle
+        .as_deref()
+        .map(|subtitle| wrap(font, subtitle, SUBTITLE_FONT_SIZE, safe_width))
+        .unwrap_or_default();
+    let subtitle_height = if subtitle_lines.is_empty() {
+        0.0
+    } else {
+        SUBTITLE_GAP + subtitle_lines.len() as f32 * SUBTITLE_FONT_SIZE * 1.2
+    };
+
+    let layout = fit_title(
+        font,
+        &params.title,
+        safe_width,
+        safe_height - subtitle_height,
+    );
+
+    let text_source = Source::Solid(params.text_color.solid_source());
+    let mut baseline_y = TEXT_MARGIN + layout.font_size;
+    for line in &layout.lines {
+        dt.draw_text(
+            font,
+            layout.font_size,
+            line,
+            Point::new(TEXT_MARGIN, baseline_y),
+            &text_source,
+            &draw_o,
+        );
+        baseline_y += layout.line_height;
+    }
+
+    if !subtitle_lines.is_empty() {
+        baseline_y += SUBTITLE_GAP;
+        for line in &subtitle_lines {
+            dt.draw_text(
+                font,
+                SUBTITLE_FONT_SIZE,
+                line,
+                Point::new(TEXT_MARGIN, baseline_y),
+                &text_source,
+                &draw_o,
+            );
+            baseline_y += SUBTITLE_FONT_SIZE * 1.2;
+        }
+    }
+
+    println!("rendering");
+    render_to(w, dt, format, 75)?;
+    println!("done");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    fn solid_image(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+    }
+
+    #[test]
+    fn resize_cover_fills_a_wider_target_by_cropping_height() {
+        // Source is taller relative to its width than the target, so the
+        // cover scale is driven by width and the crop trims the height.
+        let cover = resize_cover(&solid_image(400, 400), 1200, 300).unwrap();
+        assert_eq!((cover.width(), cover.height()), (1200, 300));
+    }
+
+    #[test]
+    fn resize_cover_fills_a_taller_target_by_cropping_width() {
+        // Source is wider relative to its height than the target, so the
+        // cover scale is driven by height and the crop trims the width.
+        let cover = resize_cover(&solid_image(400, 400), 300, 1200).unwrap();
+        assert_eq!((cover.width(), cover.height()), (300, 1200));
+    }
+
+    #[test]
+    fn resize_cover_returns_none_for_a_zero_dimension_source() {
+        assert!(resize_cover(&solid_image(0, 400), 1200, 300).is_none());
+        assert!(resize_cover(&solid_image(400, 0), 1200, 300).is_none());
+    }
+}
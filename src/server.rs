@@ -0,0 +1,191 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use font_kit::font::Font;
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::cache::ImageCache;
+use crate::client::fetch_blog_post;
+use crate::format::OutputFormat;
+use crate::params::CardParams;
+use crate::render::{load_system_font, render_params, render_thumbnail, CardSize};
+
+/// Runtime configuration for the OG-image service.
+pub struct AppConfig {
+    /// Base URL of the blog backend that serves `/api/blog`.
+    pub backend_base_url: String,
+    /// Directory where downloaded textures are cached on disk.
+    pub cache_dir: String,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Self {
+        Self {
+            backend_base_url: std::env::var("BACKEND_BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:4321".to_string()),
+            cache_dir: std::env::var("CACHE_DIR").unwrap_or_else(|_| "./cache".to_string()),
+        }
+    }
+}
+
+struct AppState {
+    client: Client,
+    cache: ImageCache,
+    config: AppConfig,
+    // Loaded once at startup: resolving the system font is too slow (and,
+    // on a host with no sans-serif font installed, too failure-prone) to
+    // redo on every request.
+    font: Font,
+}
+
+pub fn build_router(client: Client, config: AppConfig) -> anyhow::Result<Router> {
+    let cache = ImageCache::new(&config.cache_dir)?;
+    let font = load_system_font()?;
+    let state = Arc::new(AppState {
+        client,
+        cache,
+        config,
+        font,
+    });
+
+    Ok(Router::new()
+        .route("/og/:slug", get(og_thumbnail))
+        .route("/card", get(card_from_query).post(card_from_json))
+        .with_state(state))
+}
+
+#[derive(Deserialize, Default)]
+struct SizeFormatQuery {
+    format: Option<String>,
+    w: Option<i32>,
+    h: Option<i32>,
+}
+
+/// Resolve the requested output format, preferring an explicit `?format=`
+/// query param and falling back to the `Accept` header.
+fn resolve_format(format_param: Option<&str>, headers: &HeaderMap) -> OutputFormat {
+    if let Some(format) = format_param {
+        return OutputFormat::from_str_lossy(format);
+    }
+
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .and_then(OutputFormat::from_accept_header)
+        .unwrap_or(OutputFormat::Jpeg)
+}
+
+const MIN_CARD_DIMENSION: i32 = 16;
+const MAX_CARD_DIMENSION: i32 = 4096;
+
+fn resolve_size(query: &SizeFormatQuery) -> CardSize {
+    let default_size = CardSize::default();
+    CardSize {
+        width: clamp_dimension(query.w.unwrap_or(default_size.width)),
+        height: clamp_dimension(query.h.unwrap_or(default_size.height)),
+    }
+}
+
+/// Clamp a requested width/height to a sane range so an out-of-range or
+/// negative value can't reach `DrawTarget::new` or the image resizer, where
+/// it would be cast to a huge `u32`/`usize` and trigger a multi-exabyte
+/// allocation (an abort, not a catchable panic).
+fn clamp_dimension(value: i32) -> i32 {
+    value.clamp(MIN_CARD_DIMENSION, MAX_CARD_DIMENSION)
+}
+
+async fn og_thumbnail(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+    Query(query): Query<SizeFormatQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let format = resolve_format(query.format.as_deref(), &headers);
+    let size = resolve_size(&query);
+
+    let blog = fetch_blog_post(&state.client, &state.config.backend_base_url, &slug)
+        .await
+        .map_err(|err| (StatusCode::BAD_GATEWAY, err.to_string()))?;
+
+    let mut buf = Cursor::new(Vec::new());
+    render_thumbnail(
+        &mut buf,
+        &state.client,
+        &state.cache,
+        &state.font,
+        &blog,
+        format,
+        size,
+    )
+    .await
+    .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+    Ok((
+        [(header::CONTENT_TYPE, format.content_type())],
+        buf.into_inner(),
+    )
+        .into_response())
+}
+
+async fn card_from_query(
+    State(state): State<Arc<AppState>>,
+    // Two independent `Query` extractors, each parsing the full query
+    // string on its own: `#[serde(flatten)]` through `Query` mis-parses
+    // numeric fields (w/h come back as strings, failing deserialization).
+    Query(params): Query<CardParams>,
+    Query(size_format): Query<SizeFormatQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    render_card_response(&state.font, params, &size_format, &headers)
+}
+
+async fn card_from_json(
+    State(state): State<Arc<AppState>>,
+    Query(size_format): Query<SizeFormatQuery>,
+    headers: HeaderMap,
+    Json(params): Json<CardParams>,
+) -> Result<Response, (StatusCode, String)> {
+    render_card_response(&state.font, params, &size_format, &headers)
+}
+
+fn render_card_response(
+    font: &Font,
+    params: CardParams,
+    size_format: &SizeFormatQuery,
+    headers: &HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let format = resolve_format(size_format.format.as_deref(), headers);
+    let size = resolve_size(size_format);
+
+    let mut buf = Cursor::new(Vec::new());
+    render_params(&mut buf, font, &params, format, size)
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+    Ok((
+        [(header::CONTENT_TYPE, format.content_type())],
+        buf.into_inner(),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_dimension_rejects_negative_and_zero_values() {
+        assert_eq!(clamp_dimension(-100), MIN_CARD_DIMENSION);
+        assert_eq!(clamp_dimension(0), MIN_CARD_DIMENSION);
+    }
+
+    #[test]
+    fn clamp_dimension_caps_oversized_values() {
+        assert_eq!(clamp_dimension(10_000), MAX_CARD_DIMENSION);
+    }
+}
@@ -0,0 +1,118 @@
+use font_kit::font::Font;
+
+const MAX_FONT_SIZE: f32 = 96.0;
+const MIN_FONT_SIZE: f32 = 24.0;
+const FONT_STEP: f32 = 4.0;
+const LINE_HEIGHT_RATIO: f32 = 1.2;
+
+/// A word-wrapped, auto-fit block of text ready to be drawn line by line.
+pub struct TextLayout {
+    pub font_size: f32,
+    pub line_height: f32,
+    pub lines: Vec<String>,
+}
+
+fn glyph_advance(font: &Font, c: char) -> f32 {
+    font.glyph_for_char(c)
+        .and_then(|id| font.advance(id).ok())
+        .map(|advance| advance.x())
+        .unwrap_or(0.0)
+}
+
+fn measure_width(font: &Font, text: &str, font_size: f32) -> f32 {
+    let units_per_em = font.metrics().units_per_em as f32;
+    let scale = font_size / units_per_em;
+    text.chars().map(|c| glyph_advance(font, c) * scale).sum()
+}
+
+/// Break `text` into lines that each fit within `max_width` at `font_size`,
+/// breaking only on whitespace.
+fn wrap_text(font: &Font, text: &str, font_size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+
+        if current.is_empty() || measure_width(font, &candidate, font_size) <= max_width {
+            current = candidate;
+        } else {
+            lines.push(current);
+            current = word.to_string();
+        }
+    }
+
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Word-wrap `text` at a fixed `font_size`, e.g. for a subtitle drawn below
+/// the auto-fit title block.
+pub fn wrap(font: &Font, text: &str, font_size: f32, max_width: f32) -> Vec<String> {
+    wrap_text(font, text, font_size, max_width)
+}
+
+/// Word-wrap `title` within `max_width`, shrinking the font size stepwise
+/// until the wrapped block also fits within `max_height`. The size never
+/// drops below `MIN_FONT_SIZE`; at that size the block is returned even if
+/// it still overflows `max_height`.
+pub fn fit_title(font: &Font, title: &str, max_width: f32, max_height: f32) -> TextLayout {
+    let mut font_size = MAX_FONT_SIZE;
+
+    loop {
+        let lines = wrap_text(font, title, font_size, max_width);
+        let line_height = font_size * LINE_HEIGHT_RATIO;
+        let total_height = line_height * lines.len() as f32;
+
+        if total_height <= max_height || font_size <= MIN_FONT_SIZE {
+            return TextLayout {
+                font_size,
+                line_height,
+                lines,
+            };
+        }
+
+        font_size -= FONT_STEP;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use font_kit::family_name::FamilyName;
+    use font_kit::properties::Properties;
+    use font_kit::source::SystemSource;
+
+    fn test_font() -> Font {
+        SystemSource::new()
+            .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+            .unwrap()
+            .load()
+            .unwrap()
+    }
+
+    #[test]
+    fn wrap_text_keeps_an_overlong_single_word_on_its_own_line() {
+        let font = test_font();
+        let lines = wrap_text(&font, "supercalifragilisticexpialidocious", 96.0, 10.0);
+        assert_eq!(
+            lines,
+            vec!["supercalifragilisticexpialidocious".to_string()]
+        );
+    }
+
+    #[test]
+    fn fit_title_bottoms_out_at_min_font_size_instead_of_looping_forever() {
+        let font = test_font();
+        let long_title = "word ".repeat(200);
+        let layout = fit_title(&font, &long_title, 400.0, 10.0);
+        assert_eq!(layout.font_size, MIN_FONT_SIZE);
+    }
+}